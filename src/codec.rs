@@ -0,0 +1,53 @@
+use std::error::Error;
+use std::fmt;
+
+use super::network::RpcMessage;
+
+/// Wraps whatever the underlying serialization library reports, so `Codec`
+/// implementations aren't tied to `serde_json::Error` or `bincode::Error`
+/// specifically.
+#[derive(Debug)]
+pub struct CodecError(String);
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for CodecError {}
+
+/// Serializes and deserializes `RpcMessage`s on and off the wire. Swapping
+/// the `Codec` changes only the bytes a `Transport` carries; `Rpc`'s
+/// `Request`/`Response`/`RpcMessage` types are untouched either way.
+pub trait Codec: Send + Sync {
+    fn encode(&self, msg: &RpcMessage) -> Result<Vec<u8>, CodecError>;
+    fn decode(&self, bytes: &[u8]) -> Result<RpcMessage, CodecError>;
+}
+
+/// The original wire format: `RpcMessage` as UTF-8 JSON text.
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn encode(&self, msg: &RpcMessage) -> Result<Vec<u8>, CodecError> {
+        serde_json::to_vec(msg).map_err(|e| CodecError(e.to_string()))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<RpcMessage, CodecError> {
+        serde_json::from_slice(bytes).map_err(|e| CodecError(e.to_string()))
+    }
+}
+
+/// A compact binary wire format: smaller packets and no JSON text parsing on
+/// the hot decode path.
+pub struct BincodeCodec;
+
+impl Codec for BincodeCodec {
+    fn encode(&self, msg: &RpcMessage) -> Result<Vec<u8>, CodecError> {
+        bincode::serialize(msg).map_err(|e| CodecError(e.to_string()))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<RpcMessage, CodecError> {
+        bincode::deserialize(bytes).map_err(|e| CodecError(e.to_string()))
+    }
+}
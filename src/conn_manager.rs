@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, Mutex};
+
+use super::error::RpcError;
+use super::framing::encode_frame;
+use super::node::Node;
+use super::transport::TcpTlsTransport;
+
+/// Caches one live TLS connection per destination peer and funnels every
+/// outbound `RpcMessage`'s encoded bytes for that peer through a single
+/// dedicated writer task fed by an mpsc queue, so concurrent callers on the
+/// same peer never interleave partial frames on the wire. Connections are
+/// dialed lazily on first use and redialed the next time a peer is written
+/// to after its writer task has died from an I/O error.
+pub struct ConnManager {
+    transport: Arc<TcpTlsTransport>,
+    writers: Mutex<HashMap<String, Arc<Mutex<Option<mpsc::Sender<Vec<u8>>>>>>>,
+}
+
+impl ConnManager {
+    pub fn new(transport: Arc<TcpTlsTransport>) -> Self {
+        Self {
+            transport,
+            writers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Queues `encoded` to be written to `dst` on its dedicated connection,
+    /// dialing one if none is cached yet.
+    pub async fn send_to(&self, dst: &Node, encoded: Vec<u8>) -> Result<(), RpcError> {
+        let addr = dst.get_addr();
+        let sender = self.writer_for(&addr).await?;
+
+        sender.send(encoded).await.map_err(|_| {
+            RpcError::Io(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "writer task for peer is gone",
+            ))
+        })
+    }
+
+    async fn writer_for(&self, addr: &str) -> Result<mpsc::Sender<Vec<u8>>, RpcError> {
+        // Only hold the top-level map lock long enough to get (or create) this
+        // peer's slot; the slow part, dialing, happens under the per-peer slot
+        // lock below so a hung handshake with one peer can't block lookups or
+        // dials for every other peer.
+        let slot = {
+            let mut writers = self.writers.lock().await;
+            writers
+                .entry(addr.to_string())
+                .or_insert_with(|| Arc::new(Mutex::new(None)))
+                .clone()
+        };
+
+        let mut slot = slot.lock().await;
+
+        if let Some(sender) = slot.as_ref() {
+            if !sender.is_closed() {
+                return Ok(sender.clone());
+            }
+        }
+
+        let tls = self.transport.dial(addr).await.map_err(RpcError::Io)?;
+        let sender = Self::spawn_writer(addr.to_string(), tls);
+        *slot = Some(sender.clone());
+
+        Ok(sender)
+    }
+
+    fn spawn_writer(
+        addr: String,
+        mut tls: tokio_rustls::client::TlsStream<tokio::net::TcpStream>,
+    ) -> mpsc::Sender<Vec<u8>> {
+        let (tx, mut rx) = mpsc::channel::<Vec<u8>>(256);
+
+        tokio::spawn(async move {
+            while let Some(payload) = rx.recv().await {
+                if let Err(e) = tls.write_all(&encode_frame(&payload)).await {
+                    eprintln!(
+                        "[WARNING] ConnManager --> write to {addr} failed, evicting connection: {e}"
+                    );
+                    break;
+                }
+            }
+        });
+
+        tx
+    }
+}
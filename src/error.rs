@@ -0,0 +1,46 @@
+use std::fmt;
+use std::io;
+
+/// Everything that can go wrong inside [`super::network::Rpc`], surfaced as
+/// a `Result` instead of an `expect()` panic so a node can survive hostile
+/// or corrupt input from a peer.
+#[derive(Debug)]
+pub enum RpcError {
+    /// Failed to set up the transport: binding its listening socket, or
+    /// building its TLS configuration from the given certificates and key.
+    Bind(io::Error),
+    /// A send or receive on the transport failed.
+    Io(io::Error),
+    /// The codec couldn't decode an incoming payload into an `RpcMessage`.
+    Decode(String),
+    /// The codec couldn't encode an outgoing `RpcMessage`.
+    Serialize(String),
+    /// A `Mutex` guarding shared state was poisoned by a panicking holder.
+    PoisonedLock,
+    /// A message's `dst` doesn't match this node's address.
+    UnknownDestination { expected: String, got: String },
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcError::Bind(e) => write!(f, "failed to bind transport: {e}"),
+            RpcError::Io(e) => write!(f, "transport I/O error: {e}"),
+            RpcError::Decode(e) => write!(f, "failed to decode message: {e}"),
+            RpcError::Serialize(e) => write!(f, "failed to serialize message: {e}"),
+            RpcError::PoisonedLock => write!(f, "a shared lock was poisoned"),
+            RpcError::UnknownDestination { expected, got } => write!(
+                f,
+                "message destination {got} doesn't match this node's address {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+impl From<io::Error> for RpcError {
+    fn from(e: io::Error) -> Self {
+        RpcError::Io(e)
+    }
+}
@@ -0,0 +1,62 @@
+use std::io;
+
+/// Sanity cap on a single frame's declared length, so a peer can't make the
+/// decoder allocate an unbounded buffer by lying in the header.
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Prepends a 4-byte big-endian length header to `payload` before it goes
+/// out on the wire, so a stream transport (which has no datagram
+/// boundaries, unlike UDP) can tell where one `RpcMessage` ends and the
+/// next begins.
+pub fn encode_frame(payload: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(4 + payload.len());
+    framed.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    framed.extend_from_slice(payload);
+    framed
+}
+
+/// Accumulates bytes read off a stream and yields complete frames as they
+/// become available, tolerating partial headers and bodies split across
+/// multiple reads and draining several frames that arrived in a single
+/// read.
+#[derive(Default)]
+pub struct FrameDecoder {
+    buf: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed newly-read bytes into the accumulation buffer.
+    pub fn fill(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Pull exactly one complete frame out of the buffer, if one is fully
+    /// present, and advance past it. Call this in a loop after every `fill`
+    /// until it returns `Ok(None)` to drain every frame a single read may
+    /// have delivered.
+    pub fn decode_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.buf.len() < 4 {
+            return Ok(None);
+        }
+
+        let len = u32::from_be_bytes(self.buf[..4].try_into().unwrap()) as usize;
+        if len > MAX_FRAME_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "[FAILED] FrameDecoder::decode_frame --> frame length exceeds MAX_FRAME_LEN",
+            ));
+        }
+
+        if self.buf.len() < 4 + len {
+            return Ok(None);
+        }
+
+        let frame = self.buf[4..4 + len].to_vec();
+        self.buf.drain(..4 + len);
+        Ok(Some(frame))
+    }
+}
@@ -1,14 +1,19 @@
 use serde::{Deserialize, Serialize};
 
+use super::codec::{Codec, JsonCodec};
+use super::conn_manager::ConnManager;
+use super::error::RpcError;
 use super::key::Key;
 use super::node::*;
 use super::routing::{FindValueResult, NodeAndDistance};
-use super::{BUF_SIZE, TIMEOUT};
+use super::transport::{Transport, UdpTransport};
+use super::TIMEOUT;
 
 use std::collections::HashMap;
-use std::net::UdpSocket;
-use std::sync::{mpsc, Arc, Mutex};
-use std::{str, thread};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{timeout, Duration};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Request {
@@ -47,43 +52,106 @@ pub struct ReqWrapper {
     pub payload: Request,
 }
 
-#[derive(Clone, Debug)]
+/// An in-flight request's one-shot response channel. The retransmit loop in
+/// `make_request` keeps its own copy of the encoded bytes and destination in
+/// scope, so this only needs to carry the sender.
+pub struct PendingRequest {
+    pub sender: oneshot::Sender<Response>,
+}
+
+/// Initial retransmission timeout: how long `make_request` waits before its
+/// first retry if no response has arrived.
+const INITIAL_RTO: u64 = TIMEOUT / 4;
+/// Ceiling the backoff schedule is capped at, so retries don't grow unbounded.
+const MAX_RTO: u64 = TIMEOUT * 4;
+/// How many times a request is sent in all (the first send plus
+/// `MAX_ATTEMPTS - 1` retransmits), each followed by a wait, before giving up.
+const MAX_ATTEMPTS: u32 = 5;
+
+#[derive(Clone)]
 pub struct Rpc {
-    pub socket: Arc<UdpSocket>,
-    pub pending: Arc<Mutex<HashMap<Key, mpsc::Sender<Option<Response>>>>>,
+    pub transport: Arc<dyn Transport>,
+    pub codec: Arc<dyn Codec>,
+    pub pending: Arc<Mutex<HashMap<Key, PendingRequest>>>,
     pub node: Node,
+    /// When set (stream transports like `TcpTlsTransport`), requests sent by
+    /// `make_request` go through the per-peer connection cache instead of
+    /// dialing fresh on every call. `send_msg` always uses `transport`.
+    pub conn_manager: Option<Arc<ConnManager>>,
 }
 
 impl Rpc {
-    pub fn new(node: Node) -> Self {
-        let socket = UdpSocket::bind(node.get_addr())
-            .expect("[FAILED] Rpc::new --> Error while binding UdpSocket to specified addr");
+    /// Builds an `Rpc` bound to `node`'s address over the plaintext UDP
+    /// transport, wire-encoded as JSON. Use [`Rpc::with_transport`] or
+    /// [`Rpc::with_transport_and_codec`] to customize either.
+    pub async fn new(node: Node) -> Result<Self, RpcError> {
+        let transport = UdpTransport::bind(&node.get_addr())
+            .await
+            .map_err(RpcError::Bind)?;
+
+        Ok(Self::with_transport(node, Arc::new(transport)))
+    }
+
+    pub fn with_transport(node: Node, transport: Arc<dyn Transport>) -> Self {
+        Self::with_transport_and_codec(node, transport, Arc::new(JsonCodec))
+    }
 
+    pub fn with_transport_and_codec(
+        node: Node,
+        transport: Arc<dyn Transport>,
+        codec: Arc<dyn Codec>,
+    ) -> Self {
         Self {
-            socket: Arc::new(socket),
+            transport,
+            codec,
             pending: Arc::new(Mutex::new(HashMap::new())),
             node,
+            conn_manager: None,
         }
     }
-    pub fn open(rpc: Rpc, sender: mpsc::Sender<ReqWrapper>) {
-        thread::spawn(move || {
-            let mut buf = [0u8; BUF_SIZE];
 
-            loop {
-                let (len, src_addr) = rpc
-                    .socket
-                    .recv_from(&mut buf)
-                    .expect("[FAILED] Rpc::open --> Failed to receive data from peer");
+    /// Reuses connections to peers via `conn_manager` instead of dialing a
+    /// fresh one per message. Only meaningful on a stream transport.
+    pub fn with_conn_manager(mut self, conn_manager: Arc<ConnManager>) -> Self {
+        self.conn_manager = Some(conn_manager);
+        self
+    }
 
-                let payload =
-                    String::from(str::from_utf8(&buf[..len]).expect(
-                        "[FAILED] Rpc::open --> Unable to parse string from received bytes",
-                    ));
+    /// Spawns the receive loop as a tokio task. Inbound requests are handed
+    /// to `sender`, a bounded channel, so a consumer that falls behind
+    /// applies backpressure instead of letting the queue grow without
+    /// bound.
+    pub fn open(rpc: Rpc, sender: mpsc::Sender<ReqWrapper>) {
+        tokio::spawn(async move {
+            loop {
+                let (buf, src_addr) = match rpc.transport.recv().await {
+                    Ok(pair) => pair,
+                    Err(e) => {
+                        eprintln!("[WARNING] Rpc::open --> {}", RpcError::Io(e));
+                        continue;
+                    }
+                };
 
-                let mut decoded: RpcMessage = serde_json::from_str(&payload)
-                    .expect("[FAILED] Rpc::open, serde_json --> Unable to decode string payload");
+                let mut decoded: RpcMessage = match rpc.codec.decode(&buf) {
+                    Ok(decoded) => decoded,
+                    Err(e) => {
+                        eprintln!(
+                            "[WARNING] Rpc::open --> {}",
+                            RpcError::Decode(e.to_string())
+                        );
+                        continue;
+                    }
+                };
 
-                decoded.src = src_addr.to_string();
+                // On a connectionless transport (UDP) the address a message
+                // arrived from is also the peer's listening address, so it's
+                // safe to trust over whatever `src` the message claims. On a
+                // dialed stream connection (TCP) the observed address is an
+                // ephemeral port nobody listens on, so keep the message's
+                // own advertised `src` instead.
+                if rpc.transport.recv_addr_is_listen_addr() {
+                    decoded.src = src_addr;
+                }
 
                 if super::VERBOSE {
                     println!(
@@ -93,7 +161,13 @@ impl Rpc {
                 }
 
                 if decoded.dst != rpc.node.get_addr() {
-                    eprintln!("[WARNING] Rpc::open --> Destination address doesn't match node address, ignoring");
+                    eprintln!(
+                        "[WARNING] Rpc::open --> {}",
+                        RpcError::UnknownDestination {
+                            expected: rpc.node.get_addr(),
+                            got: decoded.dst.clone(),
+                        }
+                    );
                     continue;
                 }
 
@@ -108,56 +182,84 @@ impl Rpc {
                             payload: req,
                         };
 
-                        if sender.send(wrapped_req).is_err() {
+                        if sender.send(wrapped_req).await.is_err() {
                             eprintln!("[FAILED] Rpc::open, Request --> Receiver is dead, closing channel.");
                             break;
                         }
                     }
                     Message::Response(res) => {
-                        rpc.clone().handle_response(decoded.token, res);
+                        rpc.clone().handle_response(decoded.token, res).await;
                     }
                 }
             }
         });
     }
 
-    pub fn send_msg(&self, msg: &RpcMessage) {
-        let encoded = serde_json::to_string(msg)
-            .expect("[FAILED] Rpc::send_msg --> Unable to serialize message");
-        self.socket
-            .send_to(encoded.as_bytes(), &msg.dst)
-            .expect("[FAILED] Rpc::send_msg --> Error while sending message to specified address");
+    pub async fn send_msg(&self, msg: &RpcMessage) -> Result<(), RpcError> {
+        let encoded = self
+            .codec
+            .encode(msg)
+            .map_err(|e| RpcError::Serialize(e.to_string()))?;
+        self.transport
+            .send_to(&encoded, &msg.dst)
+            .await
+            .map_err(RpcError::Io)
     }
 
-    pub fn handle_response(self, token: Key, res: Response) {
-        thread::spawn(move || {
-            let mut pending = self
-                .pending
-                .lock()
-                .expect("[FAILED] Rpc::handle_response --> Failed to acquire lock on Pending");
+    /// Sends already-encoded bytes to `dst`, going through `conn_manager`'s
+    /// per-peer connection cache when one is configured, or dialing fresh on
+    /// the raw transport otherwise.
+    async fn dispatch(&self, dst: &Node, encoded: &[u8]) -> Result<(), RpcError> {
+        match &self.conn_manager {
+            Some(conn_manager) => conn_manager.send_to(dst, encoded.to_vec()).await,
+            None => self
+                .transport
+                .send_to(encoded, &dst.get_addr())
+                .await
+                .map_err(RpcError::Io),
+        }
+    }
+
+    pub async fn handle_response(self, token: Key, res: Response) {
+        tokio::spawn(async move {
+            let mut pending = match self.pending.lock() {
+                Ok(pending) => pending,
+                Err(_) => {
+                    eprintln!("[WARNING] Rpc::handle_response --> {}", RpcError::PoisonedLock);
+                    return;
+                }
+            };
 
-            let tmp = match pending.get(&token) {
-                Some(sender) => sender.send(Some(res)),
+            match pending.remove(&token) {
+                Some(pending_req) => {
+                    let _ = pending_req.sender.send(res);
+                }
                 None => {
                     eprintln!(
                         "[WARNING] Rpc::handle_response --> Unsolicited response received, ignoring..."
                     );
-                    return;
                 }
-            };
-
-            if tmp.is_ok() {
-                pending.remove(&token);
             }
         });
     }
 
-    pub fn make_request(&self, req: Request, dst: Node) -> mpsc::Receiver<Option<Response>> {
-        let (sender, receiver) = mpsc::channel();
-        let mut pending = self
-            .pending
-            .lock()
-            .expect("[FAILED] Rpc::make_request --> Failed to acquire mutex on Pending");
+    /// Sends `req` to `dst` and awaits its response, or returns `None` once
+    /// retries are exhausted.
+    ///
+    /// If no response arrives within `INITIAL_RTO`, the identical encoded
+    /// bytes are retransmitted under the same `token` on an exponential
+    /// backoff (doubling, capped at `MAX_RTO`) for up to `MAX_ATTEMPTS`
+    /// tries before giving up. Because `handle_response` removes the
+    /// pending entry on the first matching reply, a duplicate response to a
+    /// retransmit is simply ignored — but this does mean a `Store` request
+    /// may reach its destination more than once, so responders must treat
+    /// `Store` idempotently.
+    pub async fn make_request(
+        &self,
+        req: Request,
+        dst: Node,
+    ) -> Result<Option<Response>, RpcError> {
+        let (sender, mut receiver) = oneshot::channel();
 
         let token = Key::new(format!(
             "{}:{}:{:?}",
@@ -165,7 +267,6 @@ impl Rpc {
             dst.get_info(),
             std::time::SystemTime::now()
         ));
-        pending.insert(token.clone(), sender.clone());
 
         let msg = RpcMessage {
             token: token.clone(),
@@ -173,21 +274,48 @@ impl Rpc {
             dst: dst.get_addr(),
             msg: Message::Request(req),
         };
+        let encoded = self
+            .codec
+            .encode(&msg)
+            .map_err(|e| RpcError::Serialize(e.to_string()))?;
 
-        self.send_msg(&msg);
-
-        let rpc = self.clone();
-        thread::spawn(move || {
-            thread::sleep(std::time::Duration::from_millis(TIMEOUT));
-            if sender.send(None).is_ok() {
-                let mut pending = rpc
-                    .pending
-                    .lock()
-                    .expect("[FAILED] Rpc::make_request --> Failed to acquire mutex on Pending");
-                pending.remove(&token);
+        {
+            let mut pending = self.pending.lock().map_err(|_| RpcError::PoisonedLock)?;
+            pending.insert(token.clone(), PendingRequest { sender });
+        }
+
+        // If the first send fails outright (common against a dead TCP peer),
+        // don't let the `?` below propagate past the entry we just inserted —
+        // nothing else will ever remove it, so it'd leak for the rest of the
+        // node's lifetime.
+        if let Err(e) = self.dispatch(&dst, &encoded).await {
+            let mut pending = self.pending.lock().map_err(|_| RpcError::PoisonedLock)?;
+            pending.remove(&token);
+            return Err(e);
+        }
+
+        let mut rto = INITIAL_RTO;
+        for attempt in 0..MAX_ATTEMPTS {
+            match timeout(Duration::from_millis(rto), &mut receiver).await {
+                Ok(Ok(res)) => return Ok(Some(res)),
+                Ok(Err(_)) => return Ok(None), // sender dropped without a response
+                Err(_elapsed) => {
+                    // Every wait window, including this last one, ran to
+                    // completion with no response. Retransmitting again here
+                    // would just be sent into a window we'll never wait out,
+                    // so skip it on the final attempt instead of wasting it.
+                    if attempt + 1 == MAX_ATTEMPTS {
+                        break;
+                    }
+                    let _ = self.dispatch(&dst, &encoded).await;
+                    rto = (rto * 2).min(MAX_RTO);
+                }
             }
-        });
+        }
+
+        let mut pending = self.pending.lock().map_err(|_| RpcError::PoisonedLock)?;
+        pending.remove(&token);
 
-        receiver
+        Ok(None)
     }
 }
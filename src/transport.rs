@@ -0,0 +1,243 @@
+use std::io;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use rustls::server::AllowAnyAuthenticatedClient;
+use rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig, ServerName};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::sync::{mpsc, Mutex};
+use tokio_rustls::{client, TlsAcceptor, TlsConnector};
+
+use super::error::RpcError;
+use super::framing::{encode_frame, FrameDecoder};
+use super::BUF_SIZE;
+
+/// Abstracts the wire-level send/receive primitive that `Rpc` is built on, so
+/// the same `Request`/`Response`/`RpcMessage` plumbing can run over plaintext
+/// UDP or an authenticated, encrypted TCP/TLS link without `Rpc` caring which.
+/// Built on tokio so a slow or idle peer only parks a lightweight task, not
+/// an OS thread.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Send `buf` to `dst` (a `"host:port"` string, as used throughout `network`).
+    async fn send_to(&self, buf: &[u8], dst: &str) -> io::Result<()>;
+
+    /// Wait for the next message to arrive, returning its raw bytes and the
+    /// address it came from.
+    async fn recv(&self) -> io::Result<(Vec<u8>, String)>;
+
+    /// Whether the address `recv` reports is the peer's actual listening
+    /// address (true for UDP, where the socket a peer sends from is the one
+    /// it also listens on) or just an ephemeral address with no listener
+    /// behind it (false for a dialed TCP connection). `Rpc::open` uses this
+    /// to decide whether the observed address is safe to trust over the
+    /// message's own advertised `src`.
+    ///
+    /// Note this is about *addressing*, not authentication: on
+    /// `TcpTlsTransport` every connection is mutually authenticated (both
+    /// sides present and verify a certificate chaining to the shared peer
+    /// CA), but that only proves "this is a trusted node", not which
+    /// address it's reachable at — so `src` still comes from the message
+    /// itself on a stream transport, never from the verified certificate.
+    fn recv_addr_is_listen_addr(&self) -> bool;
+}
+
+/// The original plaintext UDP transport: one datagram in, one datagram out.
+pub struct UdpTransport {
+    socket: UdpSocket,
+}
+
+impl UdpTransport {
+    pub async fn bind(addr: &str) -> io::Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(addr).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for UdpTransport {
+    async fn send_to(&self, buf: &[u8], dst: &str) -> io::Result<()> {
+        self.socket.send_to(buf, dst).await?;
+        Ok(())
+    }
+
+    async fn recv(&self) -> io::Result<(Vec<u8>, String)> {
+        let mut buf = [0u8; BUF_SIZE];
+        let (len, src_addr) = self.socket.recv_from(&mut buf).await?;
+        Ok((buf[..len].to_vec(), src_addr.to_string()))
+    }
+
+    fn recv_addr_is_listen_addr(&self) -> bool {
+        true
+    }
+}
+
+/// Maps a rustls config-builder error to `RpcError::Bind`, so a malformed
+/// identity/CA (attacker-influenceable config) is returned to the caller
+/// instead of aborting the process via `expect()`.
+fn tls_config_err(e: impl std::fmt::Display) -> RpcError {
+    RpcError::Bind(io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
+}
+
+/// Mutually-authenticated TLS-over-TCP transport. Both sides of every
+/// connection present a certificate chaining to `peer_ca`, and the acceptor
+/// requires and verifies the dialer's certificate before the handshake
+/// completes — an unauthenticated party cannot get far enough to inject a
+/// single frame. A background task accepts inbound connections and funnels
+/// their bytes into `recv`, while outbound connections are dialed on demand
+/// in `send_to`.
+pub struct TcpTlsTransport {
+    connector: TlsConnector,
+    local_addr: String,
+    inbound_rx: Mutex<mpsc::Receiver<io::Result<(Vec<u8>, String)>>>,
+}
+
+impl TcpTlsTransport {
+    /// Bind `addr`. `cert_chain`/`private_key` is this node's identity;
+    /// `peer_ca` is the root every peer certificate — inbound AND outbound —
+    /// must chain to. Both the acceptor and the connector are configured for
+    /// mutual TLS: a dialer without a certificate verified against `peer_ca`
+    /// never completes the handshake, and this node likewise presents its
+    /// own certificate when dialing out.
+    pub async fn bind(
+        addr: &str,
+        cert_chain: Vec<Certificate>,
+        private_key: PrivateKey,
+        peer_ca: Certificate,
+    ) -> Result<Self, RpcError> {
+        let listener = TcpListener::bind(addr).await.map_err(RpcError::Bind)?;
+        let local_addr = listener.local_addr().map_err(RpcError::Bind)?.to_string();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(&peer_ca).map_err(tls_config_err)?;
+
+        let server_config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_client_cert_verifier(Arc::new(AllowAnyAuthenticatedClient::new(roots.clone())))
+            .with_single_cert(cert_chain.clone(), private_key.clone())
+            .map_err(tls_config_err)?;
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+
+        let client_config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_client_auth_cert(cert_chain, private_key)
+            .map_err(tls_config_err)?;
+        let connector = TlsConnector::from(Arc::new(client_config));
+
+        let (tx, rx) = mpsc::channel(256);
+        tokio::spawn(async move {
+            loop {
+                let (stream, peer_addr) = match listener.accept().await {
+                    Ok(pair) => pair,
+                    Err(e) => {
+                        if tx.send(Err(e)).await.is_err() {
+                            return;
+                        }
+                        continue;
+                    }
+                };
+
+                let tx = tx.clone();
+                let acceptor = acceptor.clone();
+                let peer = peer_addr.to_string();
+                tokio::spawn(async move {
+                    // `accept` only returns once the dialer has completed a
+                    // full mutual handshake, including presenting a client
+                    // certificate `AllowAnyAuthenticatedClient` verified
+                    // against `peer_ca` — an unauthenticated connection
+                    // never reaches the frame-reading loop below.
+                    let mut tls = match acceptor.accept(stream).await {
+                        Ok(tls) => tls,
+                        Err(e) => {
+                            let _ = tx.send(Err(io::Error::new(io::ErrorKind::Other, e))).await;
+                            return;
+                        }
+                    };
+
+                    // A connection may carry several frames back-to-back (or
+                    // none at all if the peer never writes again), so keep
+                    // reading and draining frames until it's closed or errors.
+                    let mut decoder = FrameDecoder::new();
+                    let mut chunk = [0u8; BUF_SIZE];
+                    loop {
+                        let n = match tls.read(&mut chunk).await {
+                            Ok(0) => break,
+                            Ok(n) => n,
+                            Err(e) => {
+                                let _ = tx.send(Err(e)).await;
+                                break;
+                            }
+                        };
+                        decoder.fill(&chunk[..n]);
+
+                        loop {
+                            match decoder.decode_frame() {
+                                Ok(Some(frame)) => {
+                                    if tx.send(Ok((frame, peer.clone()))).await.is_err() {
+                                        return;
+                                    }
+                                }
+                                Ok(None) => break,
+                                Err(e) => {
+                                    let _ = tx.send(Err(e)).await;
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            connector,
+            local_addr,
+            inbound_rx: Mutex::new(rx),
+        })
+    }
+
+    pub fn local_addr(&self) -> &str {
+        &self.local_addr
+    }
+
+    /// Dials a fresh, mutually-authenticated TLS connection to `dst`.
+    /// Exposed so [`super::conn_manager::ConnManager`] can hold the stream
+    /// open across several writes instead of reconnecting per message, which
+    /// `send_to` does for one-shot callers.
+    pub async fn dial(&self, dst: &str) -> io::Result<client::TlsStream<TcpStream>> {
+        let stream = TcpStream::connect(dst).await?;
+        let domain = dst.split(':').next().unwrap_or(dst);
+        let server_name = ServerName::try_from(domain)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        self.connector.connect(server_name, stream).await
+    }
+}
+
+#[async_trait]
+impl Transport for TcpTlsTransport {
+    async fn send_to(&self, buf: &[u8], dst: &str) -> io::Result<()> {
+        let mut tls = self.dial(dst).await?;
+        tls.write_all(&encode_frame(buf)).await
+    }
+
+    async fn recv(&self) -> io::Result<(Vec<u8>, String)> {
+        self.inbound_rx
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "accept loop ended"))?
+    }
+
+    fn recv_addr_is_listen_addr(&self) -> bool {
+        // The address observed in `recv` is the dialer's ephemeral TCP port,
+        // not the address it's listening on — trust the message's own `src`
+        // instead. The connection having reached this point does mean the
+        // dialer proved a certificate chaining to the shared peer CA, but
+        // that's an identity guarantee, not an addressing one.
+        false
+    }
+}